@@ -0,0 +1,38 @@
+//! A reservation for a not-yet-written slot, letting callers learn their `Id` before inserting.
+
+use crate::slot::Slot;
+use crate::{Id, MemoryStorage};
+
+/// A claimed, still-empty slot in a [`MemoryStorage`]. Obtained via [`MemoryStorage::vacant_entry`].
+///
+/// The slot is already unlinked from the free list, so its [`Id`] is final as soon as the entry
+/// is created, even though nothing has been written to it yet. This lets a value learn its own
+/// `Id` before it exists, e.g. a graph node that stores a handle to itself. If the entry is
+/// dropped without calling [`Self::insert`], the slot is leaked: it stays unreachable and isn't
+/// returned to the free list — until the next [`MemoryStorage::compact`], which reclaims any
+/// leaked slot like any other free one.
+pub struct VacantEntry<'a, T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    storage: &'a mut MemoryStorage<T, U>,
+    index: usize,
+    generation: u32,
+}
+
+impl<'a, T, U> VacantEntry<'a, T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    pub(crate) fn new(storage: &'a mut MemoryStorage<T, U>, index: usize, generation: u32) -> Self {
+        VacantEntry { storage, index, generation }
+    }
+
+    /// Returns the `Id` this entry will resolve to once [`Self::insert`] is called.
+    pub fn id(&self) -> Id {
+        Id::new(self.index, self.generation)
+    }
+
+    /// Writes `value` into the reserved slot and returns its `Id`.
+    pub fn insert(self, value: T) -> Id {
+        self.storage.fill_free_slot(self.index, self.generation, value)
+    }
+}
@@ -0,0 +1,175 @@
+//! Iterators over the items currently stored in a [`MemoryStorage`], skipping free slots.
+
+use crate::slot::Slot;
+use crate::{Id, MemoryStorage};
+use core::marker::PhantomData;
+
+/// An iterator over `(Id, &T)` pairs of all taken slots. Created by [`MemoryStorage::iter`].
+pub struct Iter<'a, T> {
+    pub(crate) slots: core::iter::Enumerate<core::slice::Iter<'a, Slot<T>>>,
+    pub(crate) remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Id, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Some(item) = slot.taken() {
+                self.remaining -= 1;
+                return Some((Id::new(index, slot.generation()), item));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// An iterator over `(Id, &mut T)` pairs of all taken slots. Created by [`MemoryStorage::iter_mut`].
+pub struct IterMut<'a, T> {
+    pub(crate) slots: core::iter::Enumerate<core::slice::IterMut<'a, Slot<T>>>,
+    pub(crate) remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Id, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            let generation = slot.generation();
+            if let Some(item) = slot.taken_mut() {
+                self.remaining -= 1;
+                return Some((Id::new(index, generation), item));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// An owning iterator over `(Id, T)` pairs of all taken slots. Created by [`MemoryStorage::into_iter`].
+pub struct IntoIter<T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    pub(crate) storage: U,
+    pub(crate) index: usize,
+    pub(crate) remaining: usize,
+    pub(crate) _marker: PhantomData<T>,
+}
+
+impl<T, U> Iterator for IntoIter<T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    type Item = (Id, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = self.storage.as_ref().len();
+        while self.index < len {
+            let index = self.index;
+            self.index += 1;
+            let slot = core::mem::replace(&mut self.storage.as_mut()[index], Slot::new_free(None, 0));
+            if slot.is_taken() {
+                let generation = slot.generation();
+                self.remaining -= 1;
+                return Some((Id::new(index, generation), slot.unwrap_taken()));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, U> MemoryStorage<T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    /// Returns an iterator over `(Id, &T)` pairs for every item currently stored, skipping free slots.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: self.storage.as_ref().iter().enumerate(),
+            remaining: self.taken_slots,
+        }
+    }
+
+    /// Returns an iterator over `(Id, &mut T)` pairs for every item currently stored, skipping free slots.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.storage.as_mut().iter_mut().enumerate(),
+            remaining: self.taken_slots,
+        }
+    }
+}
+
+impl<T, U> IntoIterator for MemoryStorage<T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    type Item = (Id, T);
+    type IntoIter = IntoIter<T, U>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let remaining = self.taken_slots;
+        IntoIter {
+            storage: self.storage,
+            index: 0,
+            remaining,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a MemoryStorage<T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    type Item = (Id, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a mut MemoryStorage<T, U>
+    where
+        U: AsRef<[Slot<T>]> + AsMut<[Slot<T>]>, {
+    type Item = (Id, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::new_with_array;
+
+    #[test]
+    fn test_iter_skips_free_slots() {
+        let mut ms = new_with_array::<i32, 3>();
+        let id = ms.insert(1).expect("I need this ID!");
+        let _ = ms.insert(2).expect("I need this ID!");
+        ms.remove(id);
+        let values: alloc::vec::Vec<i32> = ms.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, alloc::vec::Vec::from([2]));
+    }
+
+    #[test]
+    fn test_iter_mut_and_into_iter() {
+        let mut ms = new_with_array::<i32, 3>();
+        let _ = ms.insert(1).expect("I need this ID!");
+        let _ = ms.insert(2).expect("I need this ID!");
+        for (_, value) in ms.iter_mut() {
+            *value += 10;
+        }
+        let values: alloc::vec::Vec<i32> = ms.into_iter().map(|(_, value)| value).collect();
+        assert_eq!(values, alloc::vec::Vec::from([11, 12]));
+    }
+}
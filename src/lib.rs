@@ -54,15 +54,38 @@ extern crate core;
 #[cfg(feature = "alloc")]
 pub mod vec;
 
+pub mod entry;
+pub mod iter;
+pub mod remap;
 pub mod slot;
 
 use core::fmt::{Debug, Display, Formatter};
 use core::marker::PhantomData;
+use crate::remap::RemapTable;
 use crate::slot::Slot;
 
 /// The ID used to gain access to stored items.
+///
+/// With the `generational` feature on, an `Id` also carries the generation of the slot it was
+/// issued for, so a stale `Id` pointing at a slot that has since been reused no longer resolves
+/// to the wrong value.
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub struct Id(usize);
+pub struct Id {
+    pub(crate) index: usize,
+    #[cfg(feature = "generational")]
+    pub(crate) generation: u32,
+}
+
+impl Id {
+    #[allow(unused_variables)]
+    pub(crate) fn new(index: usize, generation: u32) -> Self {
+        Id {
+            index,
+            #[cfg(feature = "generational")]
+            generation,
+        }
+    }
+}
 
 /// The instance that will store all the items.
 pub struct MemoryStorage<T, U>
@@ -85,11 +108,13 @@ impl<T, U> MemoryStorage<T, U>
             return;
         }
         for i in 0..capacity {
-            self.storage
-                .as_mut()[i] = Slot::NextFreeSlot(Some(i + 1));
+            let slot = &mut self.storage.as_mut()[i];
+            let generation = if slot.is_taken() { slot.generation().wrapping_add(1) } else { slot.generation() };
+            *slot = Slot::new_free(Some(i + 1), generation);
         }
+        let generation = self.storage.as_ref()[capacity - 1].generation();
         self.storage
-            .as_mut()[capacity - 1] = Slot::NextFreeSlot(None);
+            .as_mut()[capacity - 1] = Slot::new_free(None, generation);
         self.next_free_slot = Some(0);
         self.last_free_slot = Some(capacity - 1);
         self.taken_slots = 0;
@@ -97,19 +122,22 @@ impl<T, U> MemoryStorage<T, U>
 
     /// Try to insert an item. Returning the ID on a successful insert and returning the item wrapped in an error whenever there's no space left.
     pub fn insert(&mut self, item: T) -> Result<Id, InternalStorageFullError<T>> {
-        match self.next_free_slot {
+        match self.reserve_free_slot() {
             None => Err(InternalStorageFullError(item)),
-            Some(next_free_slot) => Ok(self.fill_free_slot(next_free_slot, item)),
+            Some((free_slot, generation)) => Ok(self.fill_free_slot(free_slot, generation, item)),
         }
     }
 
-    fn fill_free_slot(&mut self, free_slot: usize, item: T) -> Id {
-        let next_free_slot = if let Slot::NextFreeSlot(next_free_slot) = self.storage.as_ref()[free_slot] {
-            next_free_slot
+    /// Claims the next free slot, unlinking it from the free list, without writing a value into it yet.
+    /// Returns the claimed slot's index together with its current generation.
+    fn reserve_free_slot(&mut self) -> Option<(usize, u32)> {
+        let free_slot = self.next_free_slot?;
+        let next_free_slot = if let Slot::NextFreeSlot { next, .. } = &self.storage.as_ref()[free_slot] {
+            *next
         } else {
             unreachable!("Slot wasn't free!");
         };
-        self.taken_slots += 1;
+        let generation = self.storage.as_ref()[free_slot].generation();
         match next_free_slot {
             None => {
                 self.next_free_slot = None;
@@ -118,24 +146,30 @@ impl<T, U> MemoryStorage<T, U>
             Some(_) =>
                 self.next_free_slot = next_free_slot,
         }
-        self.storage.as_mut()[free_slot] = Slot::Taken(item);
-        Id(free_slot)
+        Some((free_slot, generation))
     }
 
-    /// Removes an item without shifting the items after it to the left and without invalidating their IDs.
-    pub fn remove(&mut self, id: Id) -> T {
-        let id = id.0;
-        let slot = core::mem::replace(&mut self.storage.as_mut()[id], Slot::NextFreeSlot(None));
-        if slot.is_free() {
-            panic!("No item stored at index!");
-        }
-        self.taken_slots -= 1;
+    /// Writes `item` into a slot previously claimed via [`Self::reserve_free_slot`].
+    pub(crate) fn fill_free_slot(&mut self, free_slot: usize, generation: u32, item: T) -> Id {
+        self.taken_slots += 1;
+        self.storage.as_mut()[free_slot] = Slot::new_taken(item, generation);
+        Id::new(free_slot, generation)
+    }
+
+    /// Reserves the next free slot and exposes the `Id` it will resolve to *before* a value is
+    /// written into it, so the value being constructed can know its own `Id` ahead of time. Call
+    /// [`VacantEntry::insert`] to commit the value; returns `None` when the storage is at capacity.
+    pub fn vacant_entry(&mut self) -> Option<entry::VacantEntry<'_, T, U>> {
+        let (index, generation) = self.reserve_free_slot()?;
+        Some(entry::VacantEntry::new(self, index, generation))
+    }
+
+    /// Links the now-vacant slot at `id` onto the tail of the free list.
+    fn relink_free_slot(&mut self, id: usize) {
         match self.last_free_slot {
             Some(free_slot) => {
-                if let Some(slot) = self.storage.as_mut().get_mut(free_slot) {
-                    if let Slot::NextFreeSlot(next_free_slot) = slot {
-                        next_free_slot.replace(id);
-                    }
+                if let Some(Slot::NextFreeSlot { next, .. }) = self.storage.as_mut().get_mut(free_slot) {
+                    next.replace(id);
                 } else {
                     unreachable!("Slot should exist!")
                 }
@@ -146,23 +180,104 @@ impl<T, U> MemoryStorage<T, U>
                 self.last_free_slot = Some(id);
             },
         }
+    }
+
+    /// Removes an item without shifting the items after it to the left and without invalidating their IDs.
+    #[cfg(not(feature = "generational"))]
+    pub fn remove(&mut self, id: Id) -> T {
+        let index = id.index;
+        let slot = core::mem::replace(&mut self.storage.as_mut()[index], Slot::new_free(None, 0));
+        if slot.is_free() {
+            panic!("No item stored at index!");
+        }
+        self.taken_slots -= 1;
+        self.relink_free_slot(index);
         slot.unwrap_taken()
     }
 
+    /// Removes an item without shifting the items after it to the left and without invalidating their IDs.
+    /// Returns `None` instead of resolving to the wrong item whenever `id` is stale, i.e. it was
+    /// issued for a slot that has since been removed and possibly reused.
+    #[cfg(feature = "generational")]
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let index = id.index;
+        if self.storage.as_ref().get(index)?.generation() != id.generation {
+            return None;
+        }
+        let slot = core::mem::replace(
+            &mut self.storage.as_mut()[index],
+            Slot::new_free(None, id.generation.wrapping_add(1)),
+        );
+        if slot.is_free() {
+            return None;
+        }
+        self.taken_slots -= 1;
+        self.relink_free_slot(index);
+        Some(slot.unwrap_taken())
+    }
+
     /// Returns a reference to an item whenever it is present.
     pub fn get(&self, id: Id) -> Option<&T> {
-        self.storage
+        let slot = self.storage
             .as_ref()
-            .get(id.0)?
-            .taken()
+            .get(id.index)?;
+        #[cfg(feature = "generational")]
+        if slot.generation() != id.generation {
+            return None;
+        }
+        slot.taken()
     }
 
     /// Returns a mutable reference to an item whenever it is present.
     pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
-        self.storage
+        let slot = self.storage
             .as_mut()
-            .get_mut(id.0)?
-            .taken_mut()
+            .get_mut(id.index)?;
+        #[cfg(feature = "generational")]
+        if slot.generation() != id.generation {
+            return None;
+        }
+        slot.taken_mut()
+    }
+
+    /// Slides every taken item toward index 0, preserving relative order, and rebuilds the free
+    /// list as one contiguous run at the tail. This improves cache locality after heavy
+    /// remove/insert churn, at the cost of changing slot indices: every previously issued `Id` is
+    /// invalid unless translated through the returned [`RemapTable`]. With the `generational`
+    /// feature on, a stale, untranslated `Id` fails safely (`get`/`get_mut`/`remove` return
+    /// `None`) instead of resolving to whatever ends up at its old index, because a slot vacated
+    /// by the move has its generation bumped just like a regular `remove` would.
+    pub fn compact(&mut self) -> RemapTable {
+        let capacity = self.capacity;
+        let mut remap = alloc::vec![None; capacity];
+        let mut write = 0;
+        for (read, slot) in remap.iter_mut().enumerate() {
+            if self.storage.as_ref()[read].is_taken() {
+                let generation = self.storage.as_ref()[read].generation();
+                if write != read {
+                    let moved = core::mem::replace(
+                        &mut self.storage.as_mut()[read],
+                        Slot::new_free(None, generation.wrapping_add(1)),
+                    );
+                    self.storage.as_mut()[write] = moved;
+                    *slot = Some(Id::new(write, generation));
+                }
+                write += 1;
+            }
+        }
+        if write < capacity {
+            for i in write..capacity {
+                let next = if i + 1 < capacity { Some(i + 1) } else { None };
+                let generation = self.storage.as_ref()[i].generation();
+                self.storage.as_mut()[i] = Slot::new_free(next, generation);
+            }
+            self.next_free_slot = Some(write);
+            self.last_free_slot = Some(capacity - 1);
+        } else {
+            self.next_free_slot = None;
+            self.last_free_slot = None;
+        }
+        RemapTable(remap)
     }
 
     /// Returns the current capacity.
@@ -213,10 +328,10 @@ pub fn new_with_array<T, const S: usize>() -> MemoryStorage<T, SlotArray<T, S>>
 
 fn initiate_array<T, const S: usize>() -> SlotArray<T, S> {
     let mut array: [Slot<T>; S] = core::array::from_fn(|i| {
-        Slot::NextFreeSlot(Some(i + 1))
+        Slot::new_free(Some(i + 1), 0)
     });
     if S != 0 {
-        array[S - 1] = Slot::NextFreeSlot(None);
+        array[S - 1] = Slot::new_free(None, 0);
     }
     array
 }
@@ -259,6 +374,33 @@ mod tests {
         assert_eq!(ms.taken_slots, 0);
     }
 
+    #[test]
+    fn test_compact() {
+        let mut ms = new_with_array::<i32, 5>();
+        let _ = ms.insert(1).expect("I need this ID!");
+        let id_of_two = ms.insert(2).expect("I need this ID!");
+        let id_of_three = ms.insert(3).expect("I need this ID!");
+        ms.remove(id_of_two);
+        let remap = ms.compact();
+        assert_eq!(ms.taken_slots(), 2);
+        let new_id_of_three = remap.get(id_of_three).expect("Item 3 moved!");
+        assert_eq!(ms.get(new_id_of_three), Some(&3));
+        let _ = ms.insert(4).expect("Compaction should free up trailing slots!");
+        let _ = ms.insert(5).expect("Compaction should free up trailing slots!");
+        let _ = ms.insert(6).expect("Compaction should free up trailing slots!");
+        assert_eq!(ms.taken_slots(), 5);
+    }
+
+    #[test]
+    fn test_vacant_entry() {
+        let mut ms = new_with_array::<usize, 3>();
+        let entry = ms.vacant_entry().expect("Storage isn't full!");
+        let expected_id = entry.id();
+        let id = entry.insert(expected_id.index);
+        assert_eq!(id, expected_id);
+        assert_eq!(ms.get(id), Some(&expected_id.index));
+    }
+
     #[test]
     fn test_initiate_array() {
         let array = initiate_array::<(), 3>();
@@ -266,4 +408,59 @@ mod tests {
         assert_eq!(array[1].next_free(), Some(2));
         assert_eq!(array[2].next_free(), None);
     }
+
+    #[cfg(feature = "generational")]
+    #[test]
+    fn test_stale_id_after_remove() {
+        // A single-slot storage removes any ambiguity about which slot gets reused.
+        let mut ms = new_with_array::<i32, 1>();
+        let stale_id = ms.insert(1).expect("I need this ID!");
+        ms.remove(stale_id).expect("Item was just inserted!");
+        let fresh_id = ms.insert(2).expect("Slot was just freed!");
+        assert_eq!(fresh_id.index, stale_id.index);
+        assert!(ms.get(stale_id).is_none());
+        assert_eq!(ms.get(fresh_id), Some(&2));
+        assert!(ms.remove(stale_id).is_none());
+    }
+
+    #[cfg(feature = "generational")]
+    #[test]
+    fn test_stale_id_after_compact_and_reuse() {
+        let mut ms = new_with_array::<i32, 3>();
+        let _ = ms.insert(1).expect("I need this ID!");
+        let stale_id_of_two = ms.insert(2).expect("I need this ID!");
+        let id_of_three = ms.insert(3).expect("I need this ID!");
+        // Vacate the middle slot so compact() has to move item 3 into it, bumping the
+        // generation of the slot item 3 moves out of.
+        ms.remove(stale_id_of_two).expect("Item was just inserted!");
+        let remap = ms.compact();
+        let new_id_of_three = remap.get(id_of_three).expect("Item 3 moved!");
+        // Reusing the slot that item 3 moved out of must not make the original stale
+        // `Id` (generation 0) resolve again.
+        let fresh_id = ms.insert(4).expect("Compaction should free up a trailing slot!");
+        assert_eq!(fresh_id.index, id_of_three.index);
+        assert!(ms.get(id_of_three).is_none());
+        assert_eq!(ms.get(new_id_of_three), Some(&3));
+        assert_eq!(ms.get(fresh_id), Some(&4));
+    }
+
+    #[cfg(feature = "generational")]
+    #[test]
+    fn test_remap_table_rejects_id_stale_before_compact() {
+        let mut ms = new_with_array::<i32, 3>();
+        let id_of_a = ms.insert(1).expect("I need this ID!");
+        let stale_id_of_p = ms.insert(2).expect("I need this ID!");
+        let _id_of_q = ms.insert(3).expect("I need this ID!");
+        // P and A are removed *before* compact() ever runs, and the slot P vacated gets
+        // reused by M in the meantime.
+        ms.remove(stale_id_of_p).expect("Item was just inserted!");
+        ms.remove(id_of_a).expect("Item was just inserted!");
+        let id_of_m = ms.insert(4).expect("Slot was just freed!");
+        let remap = ms.compact();
+        // stale_id_of_p's slot now holds an unrelated, live item (M, possibly moved again by
+        // compact()); translating it through the remap table must not resolve to that item.
+        assert!(remap.get(stale_id_of_p).is_none());
+        let new_id_of_m = remap.get(id_of_m).expect("M moved!");
+        assert_eq!(ms.get(new_id_of_m), Some(&4));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,25 @@
+//! The [`Id`] remap table produced by [`MemoryStorage::compact`](crate::MemoryStorage::compact).
+
+use crate::Id;
+use alloc::vec::Vec;
+
+/// Maps each slot's index from just before a [`MemoryStorage::compact`](crate::MemoryStorage::compact)
+/// call to the [`Id`] it holds afterward, indexed by the old index. `None` means the slot was
+/// already free, or the item stored there didn't move.
+pub struct RemapTable(pub(crate) Vec<Option<Id>>);
+
+impl RemapTable {
+    /// Translates a stale `Id` issued before compaction into where it lives now. Returns `None`
+    /// if it pointed at a free slot, if the item didn't move (the `Id` is still valid as-is), or
+    /// if `id` itself was already stale before compaction even ran (with the `generational`
+    /// feature on) — its generation no longer matches that of the item stored at its old index
+    /// when compaction ran.
+    pub fn get(&self, id: Id) -> Option<Id> {
+        let mapped = (*self.0.get(id.index)?)?;
+        #[cfg(feature = "generational")]
+        if mapped.generation != id.generation {
+            return None;
+        }
+        Some(mapped)
+    }
+}
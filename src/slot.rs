@@ -2,13 +2,44 @@ use core::fmt::{Debug, Formatter};
 
 /// A slot representing a spot in the storage.
 pub enum Slot<T> {
-    Taken(T),
-    NextFreeSlot(Option<usize>),
+    Taken {
+        value: T,
+        /// Bumped every time this slot is vacated, so a stale [`Id`](crate::Id) pointing at a
+        /// reused slot can be told apart from the one that's actually stored there.
+        #[cfg(feature = "generational")]
+        generation: u32,
+    },
+    NextFreeSlot {
+        next: Option<usize>,
+        #[cfg(feature = "generational")]
+        generation: u32,
+    },
 }
 
 impl<T> Slot<T> {
+    /// Builds a taken slot. `generation` is ignored unless the `generational` feature is on.
+    #[allow(unused_variables)]
+    pub(crate) fn new_taken(value: T, generation: u32) -> Self {
+        Slot::Taken {
+            value,
+            #[cfg(feature = "generational")]
+            generation,
+        }
+    }
+
+    /// Builds a free slot pointing at `next`. `generation` is ignored unless the `generational`
+    /// feature is on.
+    #[allow(unused_variables)]
+    pub(crate) fn new_free(next: Option<usize>, generation: u32) -> Self {
+        Slot::NextFreeSlot {
+            next,
+            #[cfg(feature = "generational")]
+            generation,
+        }
+    }
+
     pub fn is_taken(&self) -> bool {
-        matches!(*self, Slot::Taken(_))
+        matches!(*self, Slot::Taken { .. })
     }
 
     pub fn is_free(&self) -> bool {
@@ -16,53 +47,68 @@ impl<T> Slot<T> {
     }
 
     pub fn taken(&self) -> Option<&T> {
-        if let Slot::Taken(item) = self {
-            Some(item)
+        if let Slot::Taken { value, .. } = self {
+            Some(value)
         } else {
             None
         }
     }
 
     pub fn taken_mut(&mut self) -> Option<&mut T> {
-        if let Slot::Taken(item) = self {
-            Some(item)
+        if let Slot::Taken { value, .. } = self {
+            Some(value)
         } else {
             None
         }
     }
 
     pub fn unwrap_taken(self) -> T {
-        if let Slot::Taken(kill_switch) = self {
-            kill_switch
+        if let Slot::Taken { value, .. } = self {
+            value
         } else {
             panic!("Slot wasn't taken!")
         }
     }
 
     pub fn unwrap_next_free(self) -> Option<usize> {
-        if let Slot::NextFreeSlot(next_free_slot) = self {
-            next_free_slot
+        if let Slot::NextFreeSlot { next, .. } = self {
+            next
         } else {
             panic!("Slot wasn't free!")
         }
     }
 
     pub fn next_free(&self) -> Option<usize> {
-        if let Slot::NextFreeSlot(next_free_slot) = self {
-            *next_free_slot
+        if let Slot::NextFreeSlot { next, .. } = self {
+            *next
         } else {
             panic!("Slot wasn't free!")
         }
     }
+
+    /// The slot's current generation, or `0` when the `generational` feature is off.
+    pub(crate) fn generation(&self) -> u32 {
+        #[cfg(feature = "generational")]
+        {
+            match self {
+                Slot::Taken { generation, .. } => *generation,
+                Slot::NextFreeSlot { generation, .. } => *generation,
+            }
+        }
+        #[cfg(not(feature = "generational"))]
+        {
+            0
+        }
+    }
 }
 
 impl<T: Debug> Debug for Slot<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
-            Slot::Taken(item) =>
-                write!(f, "Slot::Taken({:#?})", item),
-            Slot::NextFreeSlot(next_free_slot) =>
-                write!(f, "Slot::NextFreeSlot({:#?})", next_free_slot),
+            Slot::Taken { value, .. } =>
+                write!(f, "Slot::Taken({:#?})", value),
+            Slot::NextFreeSlot { next, .. } =>
+                write!(f, "Slot::NextFreeSlot({:#?})", next),
         }
     }
-}
\ No newline at end of file
+}
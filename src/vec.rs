@@ -24,7 +24,11 @@
 
 extern crate alloc;
 
+use alloc::collections::TryReserveError;
 use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Formatter};
+use crate::entry::VacantEntry;
+use crate::remap::RemapTable;
 use crate::{Id, MemoryStorage};
 use core::convert::AsRef;
 use core::convert::AsMut;
@@ -33,6 +37,96 @@ use crate::slot::Slot;
 /// Alias for vector containing slots.
 pub type SlotVec<T> = Vec<Slot<T>>;
 
+/// Vec operations needed to grow a [`MemoryStorage`]'s backing store, abstracted over the
+/// concrete vector type so the free-list-rewiring logic in [`grow`]/[`try_grow`]/
+/// [`extend_free_list`] only has to be written once for both [`SlotVec`] and [`SlotVecIn`].
+trait VecBackend<T>: AsRef<[Slot<T>]> + AsMut<[Slot<T>]> {
+    /// The error `try_reserve` fails with. `alloc::vec::Vec` and `allocator_api2::vec::Vec` use
+    /// distinct (incompatible) `TryReserveError` types, so this can't be a single fixed type.
+    type ReserveError;
+
+    fn push(&mut self, slot: Slot<T>);
+    fn pop(&mut self) -> Option<Slot<T>>;
+    fn capacity(&self) -> usize;
+    fn try_reserve(&mut self, additional: usize) -> Result<(), Self::ReserveError>;
+}
+
+impl<T> VecBackend<T> for SlotVec<T> {
+    type ReserveError = TryReserveError;
+
+    fn push(&mut self, slot: Slot<T>) {
+        Vec::push(self, slot)
+    }
+
+    fn pop(&mut self) -> Option<Slot<T>> {
+        Vec::pop(self)
+    }
+
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        Vec::try_reserve(self, additional)
+    }
+}
+
+/// Allocates more space for `storage`, aborting on allocation failure, and links it into the free
+/// list. Shared between [`SlotVec`]'s and [`SlotVecIn`]'s `push`.
+fn grow<T, U: VecBackend<T>>(storage: &mut MemoryStorage<T, U>) {
+    // Allow the vec to allocate more space for itself by pushing at full capacity.
+    storage.storage.push(Slot::new_free(None, 0));
+    // Remove the newly inserted value so that we can insert actual slots.
+    storage.storage.pop();
+    extend_free_list(storage);
+}
+
+/// Tries to reserve one more slot of capacity and, on success, links it into the free list.
+/// Shared between [`SlotVec`]'s and [`SlotVecIn`]'s `try_push`.
+fn try_grow<T, U: VecBackend<T>>(storage: &mut MemoryStorage<T, U>) -> Result<(), U::ReserveError> {
+    storage.storage.try_reserve(1)?;
+    extend_free_list(storage);
+    Ok(())
+}
+
+/// Links whatever spare capacity the vec currently has into the free list. Callers must have
+/// already made sure `storage.storage` has more capacity than `storage.capacity` slots.
+fn extend_free_list<T, U: VecBackend<T>>(storage: &mut MemoryStorage<T, U>) {
+    let old_capacity = storage.capacity;
+    let new_capacity = storage.storage
+        .capacity();
+    let (starting_index, slots_to_insert) = if old_capacity != 0 {
+        (old_capacity, new_capacity - old_capacity)
+    } else {
+        (0, new_capacity)
+    };
+    let mut next_free_index = starting_index;
+    for _ in 0..slots_to_insert {
+        next_free_index += 1;
+        storage.storage
+            .push(Slot::new_free(Some(next_free_index), 0));
+    }
+    // Make sure the last slot isn't pointing to a none existent slot.
+    *storage.storage
+        .as_mut()
+        .last_mut()
+        .expect("This exists!'") = Slot::new_free(None, 0);
+    match storage.last_free_slot {
+        None => {
+            storage.next_free_slot = Some(starting_index);
+            storage.last_free_slot = Some(new_capacity - 1);
+        },
+        Some(last_free_slot) => {
+            let generation = storage.storage.as_ref()[last_free_slot].generation();
+            *storage.storage
+                .as_mut()
+                .get_mut(last_free_slot)
+                .expect("This was the original last_free_slot!") = Slot::new_free(Some(starting_index), generation)
+        },
+    }
+    storage.capacity = new_capacity;
+}
+
 impl<T> MemoryStorage<T, SlotVec<T>> {
     /// Push an item ignoring capacity limits. Once the max capacity has been reached the vec simply allocates more space.
     pub fn push(&mut self, item: T) -> Id {
@@ -40,44 +134,222 @@ impl<T> MemoryStorage<T, SlotVec<T>> {
             Ok(id) => return id,
             Err(err) => err.0,
         };
-        // Allow the vec to allocate more space for itself by pushing at full capacity.
-        self.storage
-            .push(Slot::NextFreeSlot(None));
-        // Remove the newly inserted value so that we can insert actual slots.
-        self.storage.pop();
-        let old_capacity = self.capacity;
-        let new_capacity = self.storage
-            .capacity();
-        let (starting_index, slots_to_insert) = if old_capacity != 0 {
-            (old_capacity, new_capacity - old_capacity)
-        } else {
-            (0, new_capacity)
+        grow(self);
+        self.insert(item)
+            .expect("We just made space available!")
+    }
+
+    /// Try to push an item, like [`Self::push`], but growing the vec fallibly: if the allocator
+    /// can't provide more capacity, the item is handed back inside the error instead of aborting
+    /// the process.
+    pub fn try_push(&mut self, item: T) -> Result<Id, TryPushError<T>> {
+        let item = match self.insert(item) {
+            Ok(id) => return Ok(id),
+            Err(err) => err.0,
         };
-        let mut next_free_index = starting_index;
-        for _ in 0..slots_to_insert {
-            next_free_index += 1;
-            self.storage
-                .push(Slot::NextFreeSlot(Some(next_free_index)));
+        if let Err(error) = try_grow(self) {
+            return Err(TryPushError(item, error));
         }
-        // Make sure the last slot isn't pointing to a none existent slot.
-        *self.storage
-            .last_mut()
-            .expect("This exists!'") = Slot::NextFreeSlot(None);
-        match self.last_free_slot {
-            None => {
-                self.next_free_slot = Some(starting_index);
-                self.last_free_slot = Some(new_capacity - 1);
-            },
-            Some(last_free_slot) => {
-                *self.storage
-                    .get_mut(last_free_slot)
-                    .expect("This was the original last_free_slot!") = Slot::NextFreeSlot(Some(starting_index))
-            },
+        Ok(self.insert(item)
+            .expect("We just made space available!"))
+    }
+
+    /// Like [`MemoryStorage::vacant_entry`], but grows the vec instead of returning `None` whenever the storage is at capacity.
+    pub fn vacant_entry_or_grow(&mut self) -> VacantEntry<'_, T, SlotVec<T>> {
+        if self.next_free_slot.is_none() {
+            grow(self);
         }
-        self.capacity = new_capacity;
+        self.vacant_entry()
+            .expect("We just grew the vec if needed!")
+    }
+
+    /// Like [`MemoryStorage::compact`], but also shrinks the vec down to just the slots still in
+    /// use, releasing the spare capacity that used to back the free list back to the allocator.
+    pub fn compact_and_shrink_to_fit(&mut self) -> RemapTable {
+        let remap = self.compact();
+        self.storage.truncate(self.taken_slots);
+        self.storage.shrink_to_fit();
+        self.capacity = self.taken_slots;
+        self.next_free_slot = None;
+        self.last_free_slot = None;
+        remap
+    }
+}
+
+/// Error returned by [`MemoryStorage::try_push`] whenever the allocator couldn't provide more
+/// capacity. Carries the item back so the caller can recover it. `E` is the reserve error of the
+/// backing vec: `alloc::collections::TryReserveError` for [`SlotVec`], or
+/// `allocator_api2::collections::TryReserveError` for [`SlotVecIn`].
+pub struct TryPushError<T, E = TryReserveError>(pub T, pub E);
+
+impl<T, E> TryPushError<T, E> {
+    /// Returns the item that couldn't be pushed.
+    pub fn value(self) -> T {
+        self.0
+    }
+}
+
+impl<T, E: Debug> Debug for TryPushError<T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Failed to grow storage: {:?}", self.1)
+    }
+}
+
+impl<T, E: Debug> Display for TryPushError<T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// Vec-backed storage parameterized over an [`Allocator`](allocator_api2::alloc::Allocator), for
+/// placing a `MemoryStorage` in an arena, a bump allocator, or any other custom pool instead of
+/// the global allocator. Only available with the `allocator-api2` feature.
+#[cfg(feature = "allocator-api2")]
+pub type SlotVecIn<T, A> = allocator_api2::vec::Vec<Slot<T>, A>;
+
+/// Vector with a fixed size, backed by a custom [`Allocator`](allocator_api2::alloc::Allocator).
+#[cfg(feature = "allocator-api2")]
+pub struct FixedCapacitySlotVecIn<T, A: allocator_api2::alloc::Allocator>(SlotVecIn<T, A>);
+
+#[cfg(feature = "allocator-api2")]
+impl<T, A: allocator_api2::alloc::Allocator> FixedCapacitySlotVecIn<T, A> {
+    /// Acquire the inner vector.
+    pub fn vec(self) -> SlotVecIn<T, A> {
+        self.0
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<T, A: allocator_api2::alloc::Allocator> AsRef<[Slot<T>]> for FixedCapacitySlotVecIn<T, A> {
+    fn as_ref(&self) -> &[Slot<T>] {
+        self.0
+            .as_ref()
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<T, A: allocator_api2::alloc::Allocator> AsMut<[Slot<T>]> for FixedCapacitySlotVecIn<T, A> {
+    fn as_mut(&mut self) -> &mut [Slot<T>] {
+        self.0
+            .as_mut()
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<T, A: allocator_api2::alloc::Allocator> VecBackend<T> for SlotVecIn<T, A> {
+    type ReserveError = allocator_api2::collections::TryReserveError;
+
+    fn push(&mut self, slot: Slot<T>) {
+        allocator_api2::vec::Vec::push(self, slot)
+    }
+
+    fn pop(&mut self) -> Option<Slot<T>> {
+        allocator_api2::vec::Vec::pop(self)
+    }
+
+    fn capacity(&self) -> usize {
+        allocator_api2::vec::Vec::capacity(self)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), allocator_api2::collections::TryReserveError> {
+        allocator_api2::vec::Vec::try_reserve(self, additional)
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<T, A: allocator_api2::alloc::Allocator> MemoryStorage<T, SlotVecIn<T, A>> {
+    /// Push an item ignoring capacity limits, growing by allocating more space from `A`.
+    pub fn push(&mut self, item: T) -> Id {
+        let item = match self.insert(item) {
+            Ok(id) => return id,
+            Err(err) => err.0,
+        };
+        grow(self);
         self.insert(item)
             .expect("We just made space available!")
     }
+
+    /// Try to push an item, like [`Self::push`], but growing the vec fallibly: if `A` can't
+    /// provide more capacity, the item is handed back inside the error instead of aborting the
+    /// process.
+    pub fn try_push(&mut self, item: T) -> Result<Id, TryPushError<T, allocator_api2::collections::TryReserveError>> {
+        let item = match self.insert(item) {
+            Ok(id) => return Ok(id),
+            Err(err) => err.0,
+        };
+        if let Err(error) = try_grow(self) {
+            return Err(TryPushError(item, error));
+        }
+        Ok(self.insert(item)
+            .expect("We just made space available!"))
+    }
+
+    /// Like [`MemoryStorage::vacant_entry`], but grows the vec instead of returning `None` whenever the storage is at capacity.
+    pub fn vacant_entry_or_grow(&mut self) -> VacantEntry<'_, T, SlotVecIn<T, A>> {
+        if self.next_free_slot.is_none() {
+            grow(self);
+        }
+        self.vacant_entry()
+            .expect("We just grew the vec if needed!")
+    }
+}
+
+/// Create a MemoryStorage instance using a vec backed by the given allocator as storage.
+#[cfg(feature = "allocator-api2")]
+pub fn new_with_vec_in<T, A: allocator_api2::alloc::Allocator>(capacity: usize, alloc: A) -> MemoryStorage<T, SlotVecIn<T, A>> {
+    let vec = initiate_vec_in(capacity, alloc);
+    let next_free_slot;
+    let last_free_slot;
+    if capacity == 0 {
+        next_free_slot = None;
+        last_free_slot = None;
+    } else {
+        next_free_slot = Some(0);
+        last_free_slot = Some(capacity - 1)
+    }
+    MemoryStorage {
+        storage: vec,
+        next_free_slot,
+        last_free_slot,
+        taken_slots: 0,
+        capacity,
+        _marker: Default::default(),
+    }
+}
+
+/// Create a MemoryStorage instance using a vec of a fixed size, backed by the given allocator, as storage.
+#[cfg(feature = "allocator-api2")]
+pub fn new_with_fixed_capacity_vec_in<T, A: allocator_api2::alloc::Allocator>(capacity: usize, alloc: A) -> MemoryStorage<T, FixedCapacitySlotVecIn<T, A>> {
+    let fixed_capacity_slot_vec = FixedCapacitySlotVecIn(initiate_vec_in(capacity, alloc));
+    let next_free_slot;
+    let last_free_slot;
+    if capacity == 0 {
+        next_free_slot = None;
+        last_free_slot = None;
+    } else {
+        next_free_slot = Some(0);
+        last_free_slot = Some(capacity - 1)
+    }
+    MemoryStorage {
+        storage: fixed_capacity_slot_vec,
+        next_free_slot,
+        last_free_slot,
+        taken_slots: 0,
+        capacity,
+        _marker: Default::default(),
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+fn initiate_vec_in<T, A: allocator_api2::alloc::Allocator>(capacity: usize, alloc: A) -> SlotVecIn<T, A> {
+    let mut vec = allocator_api2::vec::Vec::with_capacity_in(capacity, alloc);
+    for i in 0..capacity {
+        vec.push(Slot::new_free(Some(i+1), 0));
+    }
+    if capacity != 0 {
+        vec[capacity-1] = Slot::new_free(None, 0);
+    }
+    vec
 }
 
 /// Vector with a fixed size.
@@ -151,10 +423,10 @@ pub fn new_with_vec<T>(capacity: usize) -> MemoryStorage<T, SlotVec<T>> {
 fn initiate_vec<T>(capacity: usize) -> SlotVec<T> {
     let mut vec = Vec::with_capacity(capacity);
     for i in 0..capacity {
-        vec.push(Slot::NextFreeSlot(Some(i+1)));
+        vec.push(Slot::new_free(Some(i+1), 0));
     }
     if capacity != 0 {
-        vec[capacity-1] = Slot::NextFreeSlot(None);
+        vec[capacity-1] = Slot::new_free(None, 0);
     }
     vec
 }
@@ -172,6 +444,15 @@ mod tests {
         assert_eq!(ms.taken_slots, 2);
     }
 
+    #[test]
+    fn test_try_push() {
+        let mut ms = new_with_vec(1);
+        let _ = ms.insert(());
+        assert!(ms.insert(()).is_err());
+        ms.try_push(()).expect("Allocation should succeed!");
+        assert_eq!(ms.taken_slots, 2);
+    }
+
     #[test]
     fn test_vec() {
         let mut ms = new_with_vec(3);
@@ -187,6 +468,30 @@ mod tests {
         assert_eq!(ms.taken_slots(), 6);
     }
 
+    #[test]
+    fn test_compact_and_shrink_to_fit() {
+        let mut ms = new_with_vec(5);
+        let _ = ms.insert(1).expect("I need this ID!");
+        let id_of_two = ms.insert(2).expect("I need this ID!");
+        let id_of_three = ms.insert(3).expect("I need this ID!");
+        ms.remove(id_of_two);
+        let remap = ms.compact_and_shrink_to_fit();
+        let new_id_of_three = remap.get(id_of_three).expect("Item 3 moved!");
+        assert_eq!(ms.get(new_id_of_three), Some(&3));
+        assert_eq!(ms.capacity(), ms.storage.capacity());
+        assert_eq!(ms.capacity(), 2);
+    }
+
+    #[test]
+    fn test_vacant_entry_or_grow() {
+        let mut ms = new_with_vec(0);
+        let entry = ms.vacant_entry_or_grow();
+        let expected_id = entry.id();
+        let id = entry.insert(1);
+        assert_eq!(id, expected_id);
+        assert_eq!(ms.get(id), Some(&1));
+    }
+
     #[test]
     fn test_fixed_vec() {
         let mut ms = new_with_fixed_capacity_vec(3);
@@ -199,4 +504,40 @@ mod tests {
         ms.clear();
         assert_eq!(ms.taken_slots, 0);
     }
+
+    #[cfg(feature = "allocator-api2")]
+    #[test]
+    fn test_vec_in() {
+        use crate::vec::new_with_vec_in;
+
+        let mut ms = new_with_vec_in(1, allocator_api2::alloc::Global);
+        let _ = ms.insert(());
+        let _ = ms.push(());
+        assert_eq!(ms.taken_slots, 2);
+    }
+
+    #[cfg(feature = "allocator-api2")]
+    #[test]
+    fn test_try_push_vec_in() {
+        use crate::vec::new_with_vec_in;
+
+        let mut ms = new_with_vec_in(1, allocator_api2::alloc::Global);
+        let _ = ms.insert(());
+        assert!(ms.insert(()).is_err());
+        ms.try_push(()).expect("Allocation should succeed!");
+        assert_eq!(ms.taken_slots, 2);
+    }
+
+    #[cfg(feature = "allocator-api2")]
+    #[test]
+    fn test_vacant_entry_or_grow_vec_in() {
+        use crate::vec::new_with_vec_in;
+
+        let mut ms = new_with_vec_in(0, allocator_api2::alloc::Global);
+        let entry = ms.vacant_entry_or_grow();
+        let expected_id = entry.id();
+        let id = entry.insert(1);
+        assert_eq!(id, expected_id);
+        assert_eq!(ms.get(id), Some(&1));
+    }
 }
\ No newline at end of file